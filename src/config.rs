@@ -1,14 +1,85 @@
 use anyhow::{Context, Result};
+use serde::Deserialize;
 use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// How generated commit messages should be styled
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Convention {
+	#[default]
+	Conventional,
+	Plain,
+	Gitmoji,
+}
+
+impl FromStr for Convention {
+	type Err = anyhow::Error;
+
+	/// Parse a `DISTILL_CONVENTION` value, matching the same spellings
+	/// accepted in `.distill.toml` (case-insensitively).
+	fn from_str(s: &str) -> Result<Self> {
+		match s.to_ascii_lowercase().as_str() {
+			"conventional" => Ok(Convention::Conventional),
+			"plain" => Ok(Convention::Plain),
+			"gitmoji" => Ok(Convention::Gitmoji),
+			other => anyhow::bail!("Unknown commit convention '{}', expected one of: conventional, plain, gitmoji", other),
+		}
+	}
+}
+
+/// Parse an environment variable override, returning `None` if it isn't
+/// set. A value that IS set but fails to parse is a hard error rather
+/// than a silent fall-through to the file/default value, so a typo in an
+/// env var doesn't quietly do nothing.
+fn parse_env_override<T>(key: &str) -> Result<Option<T>>
+where
+	T: FromStr,
+	T::Err: std::fmt::Display,
+{
+	match env::var(key) {
+		Ok(value) => value
+			.parse::<T>()
+			.map(Some)
+			.map_err(|e| anyhow::anyhow!("Invalid value for {} ('{}'): {}", key, value, e)),
+		Err(_) => Ok(None),
+	}
+}
+
+const DEFAULT_MODEL: &str = "openai/gpt-4o-mini";
+const DEFAULT_TEMPERATURE: f32 = 0.3;
+const DEFAULT_MAX_DIFF_CHARS: usize = 12_000;
+const DEFAULT_SYSTEM_PROMPT: &str =
+	"You are an expert software engineer writing concise, high quality git commit messages.";
+
+/// On-disk shape of `.distill.toml`. Every field is optional so a project
+/// only needs to override what it cares about.
+#[derive(Debug, Default, Deserialize)]
+struct FileConfig {
+	model: Option<String>,
+	temperature: Option<f32>,
+	max_diff_chars: Option<usize>,
+	system_prompt: Option<String>,
+	convention: Option<Convention>,
+}
 
 /// Configuration for the application
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Config {
 	pub openrouter_api_key: String,
+	pub model: String,
+	pub temperature: f32,
+	pub max_diff_chars: usize,
+	pub system_prompt: String,
+	pub convention: Convention,
 }
 
 impl Config {
-	/// Load configuration from environment variables
+	/// Load configuration from `.distill.toml` (if one can be found in or
+	/// above the current directory) and environment variables, with environment
+	/// variables taking precedence over the file.
 	pub fn load() -> Result<Self> {
 		let openrouter_api_key = env::var("OPENROUTER_API_KEY")
 			.context("OPENROUTER_API_KEY environment variable is not set. Please set it to your OpenRouter API key.")?;
@@ -17,8 +88,64 @@ impl Config {
 			anyhow::bail!("OPENROUTER_API_KEY environment variable is empty. Please provide a valid API key.");
 		}
 
+		let cwd = env::current_dir().context("Failed to get current directory")?;
+		let file_config = match Self::find_config_file(&cwd) {
+			Some(path) => Self::read_config_file(&path)?,
+			None => FileConfig::default(),
+		};
+
+		let model = env::var("DISTILL_MODEL")
+			.ok()
+			.or(file_config.model)
+			.unwrap_or_else(|| DEFAULT_MODEL.to_string());
+
+		let temperature = parse_env_override::<f32>("DISTILL_TEMPERATURE")?
+			.or(file_config.temperature)
+			.unwrap_or(DEFAULT_TEMPERATURE);
+
+		let max_diff_chars = parse_env_override::<usize>("DISTILL_MAX_DIFF_CHARS")?
+			.or(file_config.max_diff_chars)
+			.unwrap_or(DEFAULT_MAX_DIFF_CHARS);
+
+		let system_prompt = env::var("DISTILL_SYSTEM_PROMPT")
+			.ok()
+			.or(file_config.system_prompt)
+			.unwrap_or_else(|| DEFAULT_SYSTEM_PROMPT.to_string());
+
+		let convention = parse_env_override::<Convention>("DISTILL_CONVENTION")?
+			.or(file_config.convention)
+			.unwrap_or_default();
+
 		Ok(Config {
 			openrouter_api_key,
+			model,
+			temperature,
+			max_diff_chars,
+			system_prompt,
+			convention,
 		})
 	}
-} 
\ No newline at end of file
+
+	/// Walk up from `start` looking for a `.distill.toml`, stopping at the
+	/// filesystem root. Returns `None` if no config file is found.
+	fn find_config_file(start: &Path) -> Option<PathBuf> {
+		let mut dir = Some(start);
+		while let Some(current) = dir {
+			let candidate = current.join(".distill.toml");
+			if candidate.is_file() {
+				return Some(candidate);
+			}
+			dir = current.parent();
+		}
+		None
+	}
+
+	/// Parse a `.distill.toml` file into a `FileConfig`.
+	fn read_config_file(path: &Path) -> Result<FileConfig> {
+		let contents = fs::read_to_string(path)
+			.with_context(|| format!("Failed to read config file at {}", path.display()))?;
+
+		toml::from_str(&contents)
+			.with_context(|| format!("Failed to parse config file at {}", path.display()))
+	}
+}
@@ -1,7 +1,25 @@
 use anyhow::{Context, Result};
 use git2::{Repository, Signature, Status, StatusOptions};
 use log::{debug, warn};
-use std::path::Path;
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use crate::mining::mine_commit;
+use crate::openrouter::OpenRouterClient;
+
+/// Structured context about the repository's current state, gathered
+/// alongside the diff so the model can write a more informed commit
+/// message (e.g. referencing a ticket id embedded in the branch name).
+#[derive(Debug, Default, Clone)]
+pub struct RepoContext {
+	pub branch: Option<String>,
+	pub ahead: usize,
+	pub behind: usize,
+	pub added: usize,
+	pub modified: usize,
+	pub deleted: usize,
+	pub renamed: usize,
+}
 
 /// Git operations manager
 pub struct GitManager {
@@ -17,6 +35,12 @@ impl GitManager {
 		Ok(GitManager { repo })
 	}
 
+	/// The repository's `.git/hooks` directory, where git looks for hook
+	/// scripts like `prepare-commit-msg`.
+	pub fn hooks_dir(&self) -> PathBuf {
+		self.repo.path().join("hooks")
+	}
+
 	/// Check if there are any staged changes
 	pub fn has_staged_changes(&self) -> Result<bool> {
 		let mut opts = StatusOptions::new();
@@ -42,6 +66,279 @@ impl GitManager {
 		Ok(false)
 	}
 
+	/// Gather a status-summary style snapshot of the repository: the
+	/// current branch, its ahead/behind counts versus the upstream
+	/// tracking branch, and a tally of staged changes by kind.
+	pub fn repo_context(&self) -> Result<RepoContext> {
+		let (added, modified, deleted, renamed) = self.staged_file_tally()?;
+		let branch = self.current_branch()?;
+		let (ahead, behind) = self.ahead_behind()?;
+
+		Ok(RepoContext {
+			branch,
+			ahead,
+			behind,
+			added,
+			modified,
+			deleted,
+			renamed,
+		})
+	}
+
+	/// Get the name of the current branch, or `None` if HEAD is detached.
+	pub fn current_branch(&self) -> Result<Option<String>> {
+		let head = self.repo.head().context("Failed to get HEAD reference")?;
+
+		if !head.is_branch() {
+			return Ok(None);
+		}
+
+		Ok(head.shorthand().map(|s| s.to_string()))
+	}
+
+	/// Get the ahead/behind commit counts of the current branch versus its
+	/// upstream tracking branch. Returns `(0, 0)` if there is no upstream.
+	pub fn ahead_behind(&self) -> Result<(usize, usize)> {
+		let head = match self.repo.head() {
+			Ok(head) => head,
+			Err(_) => return Ok((0, 0)),
+		};
+
+		if !head.is_branch() {
+			return Ok((0, 0));
+		}
+
+		let branch_name = match head.shorthand() {
+			Some(name) => name.to_string(),
+			None => return Ok((0, 0)),
+		};
+
+		let branch = git2::Branch::wrap(head);
+		let upstream = match branch.upstream() {
+			Ok(upstream) => upstream,
+			Err(_) => {
+				debug!("Branch '{}' has no upstream, skipping ahead/behind", branch_name);
+				return Ok((0, 0));
+			}
+		};
+
+		let local_oid = branch
+			.get()
+			.target()
+			.context("Failed to resolve local branch target")?;
+		let upstream_oid = upstream
+			.get()
+			.target()
+			.context("Failed to resolve upstream branch target")?;
+
+		let (ahead, behind) = self.repo.graph_ahead_behind(local_oid, upstream_oid)
+			.context("Failed to compute ahead/behind counts")?;
+
+		Ok((ahead, behind))
+	}
+
+	/// Tally staged changes by kind: `(added, modified, deleted, renamed)`.
+	fn staged_file_tally(&self) -> Result<(usize, usize, usize, usize)> {
+		let mut opts = StatusOptions::new();
+		opts.include_ignored(false);
+		opts.include_untracked(false);
+		opts.renames_head_to_index(true);
+
+		let statuses = self.repo.statuses(Some(&mut opts))
+			.context("Failed to get git status")?;
+
+		let (mut added, mut modified, mut deleted, mut renamed) = (0, 0, 0, 0);
+
+		for entry in statuses.iter() {
+			let status = entry.status();
+			if status.contains(Status::INDEX_NEW) {
+				added += 1;
+			}
+			if status.contains(Status::INDEX_MODIFIED) || status.contains(Status::INDEX_TYPECHANGE) {
+				modified += 1;
+			}
+			if status.contains(Status::INDEX_DELETED) {
+				deleted += 1;
+			}
+			if status.contains(Status::INDEX_RENAMED) {
+				renamed += 1;
+			}
+		}
+
+		Ok((added, modified, deleted, renamed))
+	}
+
+	/// Get the list of file paths with staged changes.
+	pub fn staged_paths(&self) -> Result<Vec<String>> {
+		let mut opts = StatusOptions::new();
+		opts.include_ignored(false);
+		opts.include_untracked(false);
+
+		let statuses = self.repo.statuses(Some(&mut opts))
+			.context("Failed to get git status")?;
+
+		let mut paths = Vec::new();
+		for entry in statuses.iter() {
+			let status = entry.status();
+			if status.intersects(
+				Status::INDEX_NEW
+				| Status::INDEX_MODIFIED
+				| Status::INDEX_DELETED
+				| Status::INDEX_RENAMED
+				| Status::INDEX_TYPECHANGE
+			) {
+				if let Some(path) = entry.path() {
+					paths.push(path.to_string());
+				}
+			}
+		}
+
+		Ok(paths)
+	}
+
+	/// Stage exactly the blobs the tree at `tree_id` records for `paths`,
+	/// leaving the rest of the index untouched and never reading the
+	/// working tree. This is how `--split` re-stages a group: from a
+	/// snapshot of what the user originally staged, not from whatever is
+	/// currently on disk, so a partially-staged (`git add -p`) or
+	/// since-edited file is never promoted beyond what was actually
+	/// staged.
+	fn restage_from_tree(&mut self, tree_id: git2::Oid, paths: &[String]) -> Result<()> {
+		let tree = self.repo.find_tree(tree_id)
+			.context("Failed to load the staged tree snapshot")?;
+
+		let mut index = self.repo.index()
+			.context("Failed to get repository index")?;
+
+		for path in paths {
+			match tree.get_path(Path::new(path)) {
+				Ok(entry) => {
+					let index_entry = git2::IndexEntry {
+						ctime: git2::IndexTime::new(0, 0),
+						mtime: git2::IndexTime::new(0, 0),
+						dev: 0,
+						ino: 0,
+						mode: entry.filemode() as u32,
+						uid: 0,
+						gid: 0,
+						file_size: 0,
+						id: entry.id(),
+						flags: 0,
+						flags_extended: 0,
+						path: path.as_bytes().to_vec(),
+					};
+
+					index.add(&index_entry)
+						.with_context(|| format!("Failed to restage '{}'", path))?;
+				}
+				Err(_) => {
+					// Not in the snapshot: the path was originally staged
+					// as a deletion, so reproduce that instead.
+					index.remove_path(Path::new(path))
+						.with_context(|| format!("Failed to restage deletion of '{}'", path))?;
+				}
+			}
+		}
+
+		index.write()
+			.context("Failed to write index after restaging paths")?;
+
+		Ok(())
+	}
+
+	/// Reset the index to match HEAD, unstaging everything without
+	/// touching the working tree.
+	pub(crate) fn reset_index_to_head(&mut self) -> Result<()> {
+		let head = self.repo.head().context("Failed to get HEAD reference")?;
+		let head_tree = head.peel_to_tree().context("Failed to get HEAD tree")?;
+
+		let mut index = self.repo.index()
+			.context("Failed to get repository index")?;
+
+		index.read_tree(&head_tree)
+			.context("Failed to reset index to HEAD tree")?;
+
+		index.write()
+			.context("Failed to write index after resetting to HEAD")?;
+
+		Ok(())
+	}
+
+	/// Run `--split` mode: group the staged changes into one commit per
+	/// top-level directory (changes at the repository root form their own
+	/// group), generate a tailored commit message per group, and chain
+	/// the resulting commits onto HEAD in turn. Any originally staged
+	/// path that doesn't end up in a committed group is left staged
+	/// afterwards so nothing is silently dropped. In `dry_run` mode no
+	/// commits are created and the index is left exactly as it was found,
+	/// since this mode is a preview only. If generating or committing a
+	/// group fails, the error is returned at once and the original staging
+	/// is not restored.
+	pub async fn commit_split(&mut self, client: &OpenRouterClient, dry_run: bool) -> Result<usize> {
+		let original_paths = self.staged_paths()?;
+		if original_paths.is_empty() {
+			anyhow::bail!("No staged changes to split");
+		}
+
+		// Snapshot exactly what the user staged before tearing the index
+		// apart, so each group is restaged from these blobs rather than
+		// from whatever happens to be on disk right now.
+		let original_tree_id = {
+			let mut index = self.repo.index()
+				.context("Failed to get repository index")?;
+			index.write_tree().context("Failed to snapshot the staged tree")?
+		};
+
+		let groups = group_paths_by_top_level_dir(&original_paths);
+
+		self.reset_index_to_head()?;
+
+		let mut committed_paths: HashSet<String> = HashSet::new();
+		let mut groups_processed = 0;
+
+		for (group_name, paths) in &groups {
+			self.restage_from_tree(original_tree_id, paths)?;
+
+			if !self.has_staged_changes()? {
+				continue;
+			}
+
+			let diff = self.get_staged_diff()?;
+			let context = self.repo_context()?;
+			let message = client.generate_commit_message(&diff, &context).await
+				.with_context(|| format!("Failed to generate commit message for group '{}'", group_name))?;
+
+			println!("Group '{}':", group_name);
+			println!("------------------------");
+			println!("{}", message);
+			println!("------------------------");
+
+			if !dry_run {
+				self.commit(&message)?;
+				committed_paths.extend(paths.iter().cloned());
+			}
+			self.reset_index_to_head()?;
+
+			groups_processed += 1;
+		}
+
+		if dry_run {
+			// Nothing was committed; this was a preview, so put the index
+			// back exactly as it was found rather than leaving whatever
+			// the last previewed group staged.
+			self.restage_from_tree(original_tree_id, &original_paths)?;
+		} else {
+			let leftover: Vec<String> = original_paths.into_iter()
+				.filter(|p| !committed_paths.contains(p))
+				.collect();
+			if !leftover.is_empty() {
+				self.restage_from_tree(original_tree_id, &leftover)?;
+			}
+		}
+
+		Ok(groups_processed)
+	}
+
 	/// Stage all changes in the repository
 	pub fn stage_all_changes(&mut self) -> Result<()> {
 		let mut index = self.repo.index()
@@ -79,39 +376,45 @@ impl GitManager {
 			None,
 		).context("Failed to create diff between HEAD and index")?;
 
-		let mut diff_output = String::new();
-		
-		diff.print(git2::DiffFormat::Patch, |_delta, _hunk, line| {
-			match line.origin() {
-				'+' | '-' | ' ' => {
-					diff_output.push(line.origin());
-					if let Ok(content) = std::str::from_utf8(line.content()) {
-						diff_output.push_str(content);
-					}
-				}
-				'F' => {
-					// File header
-					if let Ok(content) = std::str::from_utf8(line.content()) {
-						diff_output.push_str("--- ");
-						diff_output.push_str(content);
-					}
-				}
-				'H' => {
-					// Hunk header
-					if let Ok(content) = std::str::from_utf8(line.content()) {
-						diff_output.push_str("@@ ");
-						diff_output.push_str(content);
-					}
-				}
-				_ => {}
-			}
-			true
-		}).context("Failed to generate diff output")?;
+		let diff_output = render_diff(&diff)?;
 
 		debug!("Generated diff with {} characters", diff_output.len());
 		Ok(diff_output)
 	}
 
+	/// Get the diff between `HEAD`'s parent and the current index — i.e.
+	/// what `--amend` would fold into the existing commit. For a root
+	/// commit (no parent) this diffs against an empty tree.
+	pub fn get_amend_diff(&self) -> Result<String> {
+		let head = self.repo.head()
+			.context("Failed to get HEAD reference")?;
+
+		let head_commit = head.peel_to_commit()
+			.context("Failed to get HEAD commit")?;
+
+		let parent_tree = match head_commit.parent(0) {
+			Ok(parent) => Some(parent.tree().context("Failed to get parent commit tree")?),
+			Err(_) => None,
+		};
+
+		let mut index = self.repo.index()
+			.context("Failed to get repository index")?;
+
+		let index_tree = self.repo.find_tree(index.write_tree()?)
+			.context("Failed to get index tree")?;
+
+		let diff = self.repo.diff_tree_to_tree(
+			parent_tree.as_ref(),
+			Some(&index_tree),
+			None,
+		).context("Failed to create amend diff")?;
+
+		let diff_output = render_diff(&diff)?;
+
+		debug!("Generated amend diff with {} characters", diff_output.len());
+		Ok(diff_output)
+	}
+
 	/// Commit the staged changes with the given message
 	pub fn commit(&self, message: &str) -> Result<()> {
 		let signature = self.get_signature()
@@ -145,6 +448,100 @@ impl GitManager {
 		Ok(())
 	}
 
+	/// Commit the staged changes with the given message, searching for a
+	/// nonce to append so the resulting commit's SHA-1 hex digest starts
+	/// with `prefix`. Returns the winning object id as a hex string. Errors
+	/// out rather than committing if no match turns up within the search
+	/// budget, so a caller never silently gets an un-mined commit.
+	pub fn commit_mined(&self, message: &str, prefix: &str) -> Result<String> {
+		let signature = self.get_signature()
+			.context("Failed to create git signature")?;
+
+		let mut index = self.repo.index()
+			.context("Failed to get repository index")?;
+
+		let tree_id = index.write_tree()
+			.context("Failed to write tree from index")?;
+
+		let tree = self.repo.find_tree(tree_id)
+			.context("Failed to find tree object")?;
+
+		let head = self.repo.head()
+			.context("Failed to get HEAD reference")?;
+
+		let parent_commit = head.peel_to_commit()
+			.context("Failed to get parent commit")?;
+
+		let buffer = self.repo.commit_create_buffer(
+			&signature,
+			&signature,
+			message,
+			&tree,
+			&[&parent_commit],
+		).context("Failed to build commit object content")?;
+
+		let commit_content = std::str::from_utf8(&buffer)
+			.context("Commit object content was not valid UTF-8")?;
+
+		let mined = mine_commit(commit_content, prefix)?
+			.with_context(|| format!(
+				"Could not find a commit hash starting with '{}' within the search budget",
+				prefix
+			))?;
+
+		let oid = self.repo.odb()
+			.context("Failed to open object database")?
+			.write(git2::ObjectType::Commit, mined.content.as_bytes())
+			.context("Failed to write mined commit object")?;
+
+		let mut head_ref = self.repo.head()
+			.context("Failed to get HEAD reference")?
+			.resolve()
+			.context("Failed to resolve HEAD to a direct reference")?;
+
+		head_ref.set_target(oid, "distill: mine")
+			.context("Failed to move HEAD to the mined commit")?;
+
+		debug!("Successfully created mined commit {} with message: {}", mined.oid_hex, message);
+		Ok(mined.oid_hex)
+	}
+
+	/// Rewrite `HEAD` with a new message, keeping its tree and parents
+	/// intact aside from the committer signature. If there are staged
+	/// changes, they are folded into the amended commit's tree first,
+	/// mirroring how `git commit --amend` behaves.
+	pub fn amend(&self, message: &str) -> Result<()> {
+		let signature = self.get_signature()
+			.context("Failed to create git signature")?;
+
+		let head = self.repo.head()
+			.context("Failed to get HEAD reference")?;
+
+		let head_commit = head.peel_to_commit()
+			.context("Failed to get HEAD commit")?;
+
+		let mut index = self.repo.index()
+			.context("Failed to get repository index")?;
+
+		let tree_id = index.write_tree()
+			.context("Failed to write tree from index")?;
+
+		let tree = self.repo.find_tree(tree_id)
+			.context("Failed to find tree object")?;
+
+		head_commit.amend(
+			Some("HEAD"),
+			None,
+			Some(&signature),
+			None,
+			Some(message),
+			Some(&tree),
+		).context("Failed to create amended commit")?;
+
+		debug!("Successfully amended commit with message: {}", message);
+		Ok(())
+	}
+
 	/// Get the git signature for commits
 	fn get_signature(&self) -> Result<Signature> {
 		// Try to get signature from git config
@@ -164,4 +561,57 @@ impl GitManager {
 		Signature::now("Distill", "distill@example.com")
 			.context("Failed to create default signature")
 	}
+}
+
+/// Render a `git2::Diff` as unified-diff text, in the same trimmed-down
+/// format used for prompting the model (file and hunk headers, plus
+/// added/removed/context lines).
+fn render_diff(diff: &git2::Diff) -> Result<String> {
+	let mut diff_output = String::new();
+
+	diff.print(git2::DiffFormat::Patch, |_delta, _hunk, line| {
+		match line.origin() {
+			'+' | '-' | ' ' => {
+				diff_output.push(line.origin());
+				if let Ok(content) = std::str::from_utf8(line.content()) {
+					diff_output.push_str(content);
+				}
+			}
+			'F' => {
+				// File header
+				if let Ok(content) = std::str::from_utf8(line.content()) {
+					diff_output.push_str("--- ");
+					diff_output.push_str(content);
+				}
+			}
+			'H' => {
+				// Hunk header
+				if let Ok(content) = std::str::from_utf8(line.content()) {
+					diff_output.push_str("@@ ");
+					diff_output.push_str(content);
+				}
+			}
+			_ => {}
+		}
+		true
+	}).context("Failed to generate diff output")?;
+
+	Ok(diff_output)
+}
+
+/// Group staged paths by their top-level path component. Files directly
+/// at the repository root are grouped together under `"."`.
+fn group_paths_by_top_level_dir(paths: &[String]) -> BTreeMap<String, Vec<String>> {
+	let mut groups: BTreeMap<String, Vec<String>> = BTreeMap::new();
+
+	for path in paths {
+		let top_level = match path.find('/') {
+			Some(idx) => path[..idx].to_string(),
+			None => ".".to_string(),
+		};
+
+		groups.entry(top_level).or_default().push(path.clone());
+	}
+
+	groups
 } 
\ No newline at end of file
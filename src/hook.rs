@@ -0,0 +1,107 @@
+use anyhow::{bail, Context, Result};
+use std::fs;
+use std::io::Write;
+use std::os::unix::fs::PermissionsExt;
+use std::path::PathBuf;
+
+use crate::git::GitManager;
+
+/// Name of the hook file we install, per git's `prepare-commit-msg` hook
+/// convention (`<hooks dir>/<name>`).
+const HOOK_FILE_NAME: &str = "prepare-commit-msg";
+
+/// Marker embedded in the hook script so `hook uninstall` (and a repeat
+/// `hook install`) can tell a distill-managed hook apart from one the
+/// user or another tool installed.
+const HOOK_MARKER: &str = "# Installed by distill - do not edit by hand";
+
+/// Install the `prepare-commit-msg` hook into the current repository's
+/// hooks directory, refusing to overwrite a conflicting hook that
+/// distill didn't install.
+pub fn install() -> Result<()> {
+	let hook_path = hook_path()?;
+
+	if hook_path.exists() {
+		let existing = fs::read_to_string(&hook_path)
+			.with_context(|| format!("Failed to read existing hook at {}", hook_path.display()))?;
+
+		if !existing.contains(HOOK_MARKER) {
+			bail!(
+				"A {} hook already exists at {} and wasn't installed by distill. Remove it yourself first if you want distill to manage it.",
+				HOOK_FILE_NAME,
+				hook_path.display()
+			);
+		}
+	}
+
+	let script = hook_script()?;
+
+	let mut file = fs::File::create(&hook_path)
+		.with_context(|| format!("Failed to create hook at {}", hook_path.display()))?;
+	file.write_all(script.as_bytes())
+		.with_context(|| format!("Failed to write hook at {}", hook_path.display()))?;
+
+	let mut perms = file.metadata()
+		.with_context(|| format!("Failed to read permissions for {}", hook_path.display()))?
+		.permissions();
+	perms.set_mode(0o755);
+	fs::set_permissions(&hook_path, perms)
+		.with_context(|| format!("Failed to make hook executable at {}", hook_path.display()))?;
+
+	println!("✅ Installed {} hook at {}", HOOK_FILE_NAME, hook_path.display());
+	Ok(())
+}
+
+/// Remove the previously installed hook, refusing to touch one that
+/// wasn't installed by distill.
+pub fn uninstall() -> Result<()> {
+	let hook_path = hook_path()?;
+
+	if !hook_path.exists() {
+		println!("No {} hook is installed, nothing to do.", HOOK_FILE_NAME);
+		return Ok(());
+	}
+
+	let existing = fs::read_to_string(&hook_path)
+		.with_context(|| format!("Failed to read existing hook at {}", hook_path.display()))?;
+
+	if !existing.contains(HOOK_MARKER) {
+		bail!(
+			"The {} hook at {} wasn't installed by distill, refusing to remove it.",
+			HOOK_FILE_NAME,
+			hook_path.display()
+		);
+	}
+
+	fs::remove_file(&hook_path)
+		.with_context(|| format!("Failed to remove hook at {}", hook_path.display()))?;
+
+	println!("✅ Removed {} hook at {}", HOOK_FILE_NAME, hook_path.display());
+	Ok(())
+}
+
+/// Path to the hook file in the current repository's hooks directory.
+fn hook_path() -> Result<PathBuf> {
+	let git_manager = GitManager::new(".")?;
+	Ok(git_manager.hooks_dir().join(HOOK_FILE_NAME))
+}
+
+/// Build the hook script, pointing it at the currently running distill
+/// binary so it works regardless of `PATH`. Git invokes
+/// `prepare-commit-msg` as `<hook> <msg-file> [source] [sha]`; we forward
+/// the message file and source straight through to `--emit-message-file`
+/// / `--commit-source`, which skip merge and amend commits themselves.
+fn hook_script() -> Result<String> {
+	let exe = std::env::current_exe()
+		.context("Failed to determine the path to the distill binary")?;
+
+	Ok(format!(
+		"#!/bin/sh\n\
+		{marker}\n\
+		# Reinstall with `distill hook install`, remove with `distill hook uninstall`.\n\
+		\n\
+		exec \"{exe}\" --emit-message-file \"$1\" --commit-source \"${{2:-}}\"\n",
+		marker = HOOK_MARKER,
+		exe = exe.display(),
+	))
+}
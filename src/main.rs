@@ -1,9 +1,13 @@
 use anyhow::{Context, Result};
-use clap::Parser;
+use clap::{Parser, Subcommand};
 use log::info;
+use std::fs;
+use std::path::{Path, PathBuf};
 
 mod config;
 mod git;
+mod hook;
+mod mining;
 mod openrouter;
 
 use config::Config;
@@ -14,6 +18,9 @@ use openrouter::OpenRouterClient;
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
 struct Args {
+	#[command(subcommand)]
+	command: Option<Command>,
+
 	/// Don't automatically stage changes if no changes are currently staged
 	#[arg(long)]
 	no_auto_stage: bool,
@@ -25,6 +32,51 @@ struct Args {
 	/// Verbose output
 	#[arg(short, long)]
 	verbose: bool,
+
+	/// Split the staged changes into multiple commits, one per top-level
+	/// directory, each with its own AI-generated message
+	#[arg(long)]
+	split: bool,
+
+	/// Regenerate the message of the last commit (HEAD) instead of
+	/// creating a new one, folding in any newly staged changes
+	#[arg(long)]
+	amend: bool,
+
+	/// Mine a nonce so the final commit's SHA-1 hex digest starts with
+	/// this prefix (e.g. `dead`). Incompatible with --split (which
+	/// produces more than one commit) and --amend.
+	#[arg(long, value_name = "PREFIX")]
+	mine: Option<String>,
+
+	/// Emit message only mode: write the generated message to this file
+	/// instead of committing. Used internally by the `prepare-commit-msg`
+	/// hook installed via `distill hook install`.
+	#[arg(long, hide = true, value_name = "PATH")]
+	emit_message_file: Option<PathBuf>,
+
+	/// The commit source git passed to the `prepare-commit-msg` hook
+	/// (e.g. `message`, `template`, `merge`, `squash`, `commit`). Only
+	/// meaningful alongside `--emit-message-file`.
+	#[arg(long, hide = true, value_name = "SOURCE")]
+	commit_source: Option<String>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+	/// Manage the `prepare-commit-msg` git hook integration
+	Hook {
+		#[command(subcommand)]
+		action: HookAction,
+	},
+}
+
+#[derive(Subcommand, Debug)]
+enum HookAction {
+	/// Install the prepare-commit-msg hook into .git/hooks
+	Install,
+	/// Remove the previously installed hook
+	Uninstall,
 }
 
 #[tokio::main]
@@ -40,6 +92,17 @@ async fn main() -> Result<()> {
 		env_logger::init();
 	}
 
+	if let Some(Command::Hook { action }) = &args.command {
+		return match action {
+			HookAction::Install => hook::install(),
+			HookAction::Uninstall => hook::uninstall(),
+		};
+	}
+
+	if let Some(path) = &args.emit_message_file {
+		return emit_message(args.commit_source.as_deref(), path).await;
+	}
+
 	// Load configuration
 	let config = Config::load()?;
 	info!("Configuration loaded successfully");
@@ -52,33 +115,112 @@ async fn main() -> Result<()> {
 	let has_staged_changes = git_manager.has_staged_changes()?;
 	info!("Staged changes detected: {}", has_staged_changes);
 
-	if !has_staged_changes {
+	// Track whether distill itself populated the index, so that if this
+	// run ends up not committing (dry-run, or an early abort), we can put
+	// the index back exactly how the user left it.
+	let mut auto_staged = false;
+
+	if !has_staged_changes && !args.amend {
 		if args.no_auto_stage {
 			anyhow::bail!("No staged changes found and --no-auto-stage flag is set. Please stage some changes first.");
 		}
-		
+
 		info!("No staged changes found, staging all changes...");
 		git_manager.stage_all_changes()?;
-		
+		auto_staged = true;
+
 		// Double-check that we have changes after staging
 		if !git_manager.has_staged_changes()? {
 			anyhow::bail!("No changes to commit after staging all files.");
 		}
 	}
 
-	// Get the diff of staged changes
-	let diff = git_manager.get_staged_diff()?;
-	info!("Retrieved staged diff ({} characters)", diff.len());
+	// Run the rest of the flow, then restore the index if distill was the
+	// one that populated it and nothing ended up getting committed
+	// (dry-run, or an error partway through).
+	let committed = run(&args, &config, &mut git_manager).await;
+
+	if auto_staged && !matches!(committed, Ok(true)) {
+		git_manager.reset_index_to_head()
+			.context("Failed to restore the index after an aborted run")?;
+	}
+
+	committed.map(|_| ())
+}
+
+/// Generate a commit message (and, unless `--dry-run`, act on it) for
+/// whatever is currently staged. Returns whether a commit was actually
+/// made, so the caller can decide whether auto-staged changes need to be
+/// unstaged again.
+async fn run(args: &Args, config: &Config, git_manager: &mut GitManager) -> Result<bool> {
+	if args.mine.is_some() && args.split {
+		anyhow::bail!("--mine cannot be combined with --split, which produces more than one commit");
+	}
+	if args.mine.is_some() && args.amend {
+		anyhow::bail!("--mine cannot be combined with --amend");
+	}
+
+	// Get the diff to summarize: for `--amend` this is HEAD's parent
+	// versus the index (i.e. what the amended commit would contain),
+	// otherwise it's the plain staged diff.
+	let diff = if args.amend {
+		git_manager.get_amend_diff()?
+	} else {
+		git_manager.get_staged_diff()?
+	};
+	info!("Retrieved diff ({} characters)", diff.len());
 
 	if diff.trim().is_empty() {
 		anyhow::bail!("No staged changes found to generate commit message for.");
 	}
 
+	let openrouter_client = OpenRouterClient::new(config);
+
+	if args.amend {
+		info!("Regenerating message for HEAD...");
+		let repo_context = git_manager.repo_context()?;
+		let commit_message = openrouter_client.generate_commit_message(&diff, &repo_context).await
+			.context("Failed to generate commit message from OpenRouter API")?;
+
+		println!("Generated commit message:");
+		println!("------------------------");
+		println!("{}", commit_message);
+		println!("------------------------");
+
+		if args.dry_run {
+			println!("Dry run mode - commit message generated but HEAD was not amended.");
+			return Ok(false);
+		}
+
+		git_manager.amend(&commit_message)?;
+		println!("✅ Successfully amended HEAD with AI-generated message!");
+
+		return Ok(true);
+	}
+
+	if args.split {
+		info!("Splitting staged changes into per-directory commits...");
+		let groups_processed = git_manager.commit_split(&openrouter_client, args.dry_run).await
+			.context("Failed to split staged changes into commits")?;
+
+		if args.dry_run {
+			println!("Dry run mode - {} group(s) previewed but not committed.", groups_processed);
+			return Ok(false);
+		}
+
+		println!("✅ Successfully split staged changes into {} commit(s)!", groups_processed);
+		return Ok(true);
+	}
+
+	// Gather repository context (branch, ahead/behind, staged tally) to
+	// ground the generated message
+	let repo_context = git_manager.repo_context()?;
+	info!("Repository context: {:?}", repo_context);
+
 	// Generate commit message using OpenRouter
-	let openrouter_client = OpenRouterClient::new(&config.openrouter_api_key);
 	info!("Generating commit message...");
-	
-	let commit_message = openrouter_client.generate_commit_message(&diff).await
+
+	let commit_message = openrouter_client.generate_commit_message(&diff, &repo_context).await
 		.context("Failed to generate commit message from OpenRouter API")?;
 
 	println!("Generated commit message:");
@@ -88,14 +230,75 @@ async fn main() -> Result<()> {
 
 	if args.dry_run {
 		println!("Dry run mode - commit message generated but not committed.");
-		return Ok(());
+		return Ok(false);
 	}
 
 	// Commit with the generated message
 	info!("Committing changes...");
-	git_manager.commit(&commit_message)?;
-	
-	println!("✅ Successfully committed changes with AI-generated message!");
-	
+
+	if let Some(prefix) = &args.mine {
+		info!("Mining for a commit hash starting with '{}'...", prefix);
+		let oid = git_manager.commit_mined(&commit_message, prefix)?;
+		println!("✅ Successfully committed changes with AI-generated message (mined {})!", oid);
+	} else {
+		git_manager.commit(&commit_message)?;
+		println!("✅ Successfully committed changes with AI-generated message!");
+	}
+
+	Ok(true)
+}
+
+/// "Emit message only" mode, used by the installed `prepare-commit-msg`
+/// hook: generate a commit message for whatever git has already staged
+/// and write it to the message file path git passes as `$1`, instead of
+/// creating a commit. A failing hook must never block the user's `git
+/// commit`, so errors are logged to stderr and swallowed rather than
+/// propagated.
+async fn emit_message(commit_source: Option<&str>, message_file: &Path) -> Result<()> {
+	// Don't clobber a message git already prepared or the user already
+	// supplied: an amend, a merge/squash commit, a template, or an
+	// explicit `-m`/`-F` message.
+	if matches!(
+		commit_source,
+		Some("merge") | Some("commit") | Some("message") | Some("squash") | Some("template")
+	) {
+		info!("Skipping message generation for commit source '{:?}'", commit_source);
+		return Ok(());
+	}
+
+	if let Err(err) = try_emit_message(message_file).await {
+		eprintln!("distill: could not generate a commit message ({:#}), leaving the default message in place", err);
+	}
+
 	Ok(())
-} 
\ No newline at end of file
+}
+
+async fn try_emit_message(message_file: &Path) -> Result<()> {
+	let config = Config::load()?;
+	let git_manager = GitManager::new(".")?;
+
+	if !git_manager.has_staged_changes()? {
+		return Ok(());
+	}
+
+	let diff = git_manager.get_staged_diff()?;
+	if diff.trim().is_empty() {
+		return Ok(());
+	}
+
+	let repo_context = git_manager.repo_context()?;
+	let openrouter_client = OpenRouterClient::new(&config);
+	let commit_message = openrouter_client.generate_commit_message(&diff, &repo_context).await
+		.context("Failed to generate commit message from OpenRouter API")?;
+
+	// Git already wrote a template to this file (comments, and the
+	// `commit.verbose` diff if enabled) before invoking the hook; prepend
+	// our message above that rather than clobbering it.
+	let existing = fs::read_to_string(message_file)
+		.with_context(|| format!("Failed to read existing message file at {}", message_file.display()))?;
+
+	fs::write(message_file, format!("{}\n\n{}", commit_message.trim(), existing))
+		.with_context(|| format!("Failed to write generated message to {}", message_file.display()))?;
+
+	Ok(())
+}
@@ -0,0 +1,83 @@
+use anyhow::{bail, Result};
+use log::debug;
+use sha1::{Digest, Sha1};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How long a single mining search is allowed to run before giving up.
+const SEARCH_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// How many nonces a single worker thread will try before giving up,
+/// independent of the timeout, so an unreachable prefix still fails
+/// promptly on a fast machine.
+const MAX_ATTEMPTS_PER_WORKER: u64 = 20_000_000;
+
+/// A commit object whose content was mined to produce a chosen hash
+/// prefix: the exact bytes to write to the object database, and the
+/// resulting object id as a hex string.
+pub struct MinedCommit {
+	pub content: String,
+	pub oid_hex: String,
+}
+
+/// Search for a nonce to append to `commit_content` as a trailer line
+/// such that the resulting commit object's SHA-1 hex digest starts with
+/// `prefix`. Spawns one worker thread per available core, each scanning
+/// a disjoint range of nonces, and stops as soon as any worker finds a
+/// match. Returns `Ok(None)` if nothing turns up within the search
+/// budget, rather than searching forever for an unreachable prefix.
+pub fn mine_commit(commit_content: &str, prefix: &str) -> Result<Option<MinedCommit>> {
+	if prefix.is_empty() || !prefix.bytes().all(|b| b.is_ascii_hexdigit()) {
+		bail!("Vanity prefix '{}' must be non-empty hex (0-9, a-f)", prefix);
+	}
+	let prefix = prefix.to_ascii_lowercase();
+
+	let worker_count = thread::available_parallelism().map(|n| n.get()).unwrap_or(4);
+	debug!("Mining for commit hash prefix '{}' with {} worker thread(s)", prefix, worker_count);
+
+	let found = AtomicBool::new(false);
+	let winner: Mutex<Option<MinedCommit>> = Mutex::new(None);
+	let deadline = Instant::now() + SEARCH_TIMEOUT;
+
+	thread::scope(|scope| {
+		for worker_id in 0..worker_count {
+			let found = &found;
+			let winner = &winner;
+			let prefix = prefix.as_str();
+			scope.spawn(move || {
+				let mut nonce = worker_id as u64;
+				let mut attempts = 0u64;
+
+				while !found.load(Ordering::Relaxed)
+					&& attempts < MAX_ATTEMPTS_PER_WORKER
+					&& Instant::now() < deadline
+				{
+					let candidate = format!("{}\n\nnonce: {}\n", commit_content.trim_end_matches('\n'), nonce);
+					let oid_hex = git_object_sha1_hex("commit", candidate.as_bytes());
+
+					if oid_hex.starts_with(prefix) && !found.swap(true, Ordering::SeqCst) {
+						*winner.lock().unwrap() = Some(MinedCommit { content: candidate, oid_hex });
+						break;
+					}
+
+					nonce += worker_count as u64;
+					attempts += 1;
+				}
+			});
+		}
+	});
+
+	Ok(winner.into_inner().unwrap())
+}
+
+/// Compute the hex SHA-1 object id git would assign to a loose object of
+/// the given type, i.e. `sha1("<kind> <len>\0<data>")`.
+fn git_object_sha1_hex(kind: &str, data: &[u8]) -> String {
+	let mut hasher = Sha1::new();
+	hasher.update(format!("{} {}\0", kind, data.len()).as_bytes());
+	hasher.update(data);
+
+	hasher.finalize().iter().map(|b| format!("{:02x}", b)).collect()
+}
@@ -0,0 +1,149 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::config::{Config, Convention};
+use crate::git::RepoContext;
+
+const OPENROUTER_URL: &str = "https://openrouter.ai/api/v1/chat/completions";
+
+/// Client for talking to the OpenRouter chat completions API
+pub struct OpenRouterClient {
+	http: reqwest::Client,
+	api_key: String,
+	model: String,
+	temperature: f32,
+	system_prompt: String,
+	convention: Convention,
+	max_diff_chars: usize,
+}
+
+impl OpenRouterClient {
+	/// Create a new client from the loaded configuration
+	pub fn new(config: &Config) -> Self {
+		OpenRouterClient {
+			http: reqwest::Client::new(),
+			api_key: config.openrouter_api_key.clone(),
+			model: config.model.clone(),
+			temperature: config.temperature,
+			system_prompt: config.system_prompt.clone(),
+			convention: config.convention,
+			max_diff_chars: config.max_diff_chars,
+		}
+	}
+
+	/// Generate a commit message for the given staged diff and repository
+	/// context
+	pub async fn generate_commit_message(&self, diff: &str, context: &RepoContext) -> Result<String> {
+		let prompt = self.build_prompt(&truncate_diff(diff, self.max_diff_chars), context);
+
+		let request = ChatRequest {
+			model: self.model.clone(),
+			temperature: self.temperature,
+			messages: vec![
+				ChatMessage {
+					role: "system".to_string(),
+					content: self.system_prompt.clone(),
+				},
+				ChatMessage {
+					role: "user".to_string(),
+					content: prompt,
+				},
+			],
+		};
+
+		let response = self
+			.http
+			.post(OPENROUTER_URL)
+			.bearer_auth(&self.api_key)
+			.json(&request)
+			.send()
+			.await
+			.context("Failed to send request to OpenRouter API")?
+			.error_for_status()
+			.context("OpenRouter API returned an error status")?;
+
+		let body: ChatResponse = response
+			.json()
+			.await
+			.context("Failed to parse OpenRouter API response")?;
+
+		let message = body
+			.choices
+			.into_iter()
+			.next()
+			.context("OpenRouter API returned no choices")?
+			.message
+			.content;
+
+		Ok(message.trim().to_string())
+	}
+
+	/// Build the user-turn prompt, steering the model's output format
+	/// according to the configured commit message convention and
+	/// grounding it in the current repository context.
+	fn build_prompt(&self, diff: &str, context: &RepoContext) -> String {
+		let convention_instructions = match self.convention {
+			Convention::Conventional => {
+				"Follow the Conventional Commits format (e.g. `feat: ...`, `fix: ...`)."
+			}
+			Convention::Plain => "Write a plain, imperative-mood summary with no prefix.",
+			Convention::Gitmoji => {
+				"Start the summary with a single relevant gitmoji (e.g. `:sparkles:`)."
+			}
+		};
+
+		format!(
+			"{}\n\nRepository context:\n{}\n\nWrite a commit message for the following staged diff:\n\n{}",
+			convention_instructions,
+			format_context(context),
+			diff
+		)
+	}
+}
+
+/// Render a `RepoContext` as a short block of text for the prompt.
+fn format_context(context: &RepoContext) -> String {
+	let branch = context.branch.as_deref().unwrap_or("detached HEAD");
+
+	format!(
+		"- branch: {} (ahead {}, behind {})\n- staged: {} added, {} modified, {} deleted, {} renamed",
+		branch, context.ahead, context.behind, context.added, context.modified, context.deleted, context.renamed
+	)
+}
+
+/// Truncate a diff to its first `max_chars` characters, respecting char
+/// boundaries, so oversized diffs don't blow the model's context window.
+/// A truncated diff gets a short "diff truncated" marker appended after
+/// those characters.
+fn truncate_diff(diff: &str, max_chars: usize) -> String {
+	if diff.chars().count() <= max_chars {
+		return diff.to_string();
+	}
+
+	let mut truncated: String = diff.chars().take(max_chars).collect();
+	truncated.push_str("\n... (diff truncated)");
+	truncated
+}
+
+#[derive(Debug, Serialize)]
+struct ChatRequest {
+	model: String,
+	temperature: f32,
+	messages: Vec<ChatMessage>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ChatMessage {
+	role: String,
+	content: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatResponse {
+	choices: Vec<ChatChoice>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatChoice {
+	message: ChatMessage,
+}